@@ -1,12 +1,16 @@
 use std::fs::{File, OpenOptions};
 use std::io::Read;
 use std::io::Write;
+use std::cell::Cell;
+use std::io::{Seek, SeekFrom};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::os::unix::prelude::*;
 use std::path::Path;
+use std::rc::Rc;
 use std::slice::{from_raw_parts, from_raw_parts_mut};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use bincode::Options;
 use bytemuck::cast_slice;
@@ -16,33 +20,154 @@ use mmarinus::Known;
 use mmarinus::{perms, sealed::Type, Kind, Map};
 use serde::{Deserialize, Serialize};
 
+/// Access-pattern hint passed to [`MmapFile::advise`], wrapping `madvise(2)`.
+#[derive(Clone, Copy, Debug)]
+pub enum Advice {
+    Sequential,
+    Random,
+    WillNeed,
+    DontNeed,
+}
+
+impl Advice {
+    fn as_raw(self) -> libc::c_int {
+        match self {
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+        }
+    }
+}
+
 pub trait Mmap<'a> {
     fn size(&self) -> usize;
     fn as_slice(&self) -> &'a [u8];
     fn as_slice_mut(&mut self) -> &'a mut [u8];
+
+    /// `msync` the `[offset, offset + len)` byte range. `offset` must be
+    /// page-aligned. Backends without a file (anonymous/volatile) no-op.
+    fn flush(&self, _offset: usize, _len: usize, _sync: bool) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// `madvise` the `[offset, offset + len)` byte range. Backends that can't
+    /// honour the hint no-op.
+    fn advise(&self, _offset: usize, _len: usize, _advice: Advice) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `msync` a byte range relative to a mapped base address.
+fn msync_range(addr: *mut u8, offset: usize, len: usize, sync: bool) -> std::io::Result<()> {
+    let flag = if sync { libc::MS_SYNC } else { libc::MS_ASYNC };
+    let ret = unsafe { libc::msync(addr.add(offset) as *mut libc::c_void, len, flag) };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// `madvise` a byte range relative to a mapped base address.
+fn madvise_range(addr: *mut u8, offset: usize, len: usize, advice: Advice) -> std::io::Result<()> {
+    let ret = unsafe { libc::madvise(addr.add(offset) as *mut libc::c_void, len, advice.as_raw()) };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Magic of the original, unversioned layout (`magic, typename, size`). Files
+/// carrying it are read as format version 0.
+const MAGIC_V0: &[u8; 4] = b"MMAP";
+/// Magic of the current, extensible layout (adds version, flags and checksum).
+const MAGIC_V1: &[u8; 4] = b"MMP1";
+/// Current on-disk format version.
+const CURRENT_VERSION: u16 = 1;
+
+/// Storage-semantics bits carried in [`MmapFileHdr::flags`].
+pub mod flags {
+    /// Backing is volatile/anonymous rather than a persistent file.
+    pub const VOLATILE: u64 = 1 << 0;
+    /// Data region is compressed.
+    pub const COMPRESSED: u64 = 1 << 1;
+    /// File is an append-log ([`MmapLog`](crate::MmapLog)) rather than a plain
+    /// array.
+    pub const APPEND_LOG: u64 = 1 << 2;
+    /// Data was produced on a big-endian host. Absent means little-endian.
+    pub const BIG_ENDIAN: u64 = 1 << 3;
+}
+
+/// Whether the host is big-endian.
+const fn host_big_endian() -> bool {
+    cfg!(target_endian = "big")
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Byte-order swap for the scalar element types that can be normalized across
+/// architectures; `swap_bytes` returns the swapped value. `Pod` alone can't express this, so callers opt in by
+/// choosing an element type that implements it.
+pub trait ByteSwap {
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! impl_byteswap {
+    ($($t:ty),*) => {
+        $(impl ByteSwap for $t {
+            fn swap_bytes(self) -> Self { <$t>::swap_bytes(self) }
+        })*
+    };
+}
+impl_byteswap!(u16, u32, u64, u128, i16, i32, i64, i128);
+
+impl ByteSwap for f32 {
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl ByteSwap for f64 {
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+#[derive(Debug)]
 pub struct MmapFileHdr {
     magic: [u8; 4],
+    version: u16,
+    flags: u64,
     typename: String,
     size: u64,
+    checksum: u64,
 }
 
 impl MmapFileHdr {
     fn new<T: Pod>(size: u64) -> Self {
+        Self::named(std::any::type_name::<T>().into(), size)
+    }
+
+    /// Header for a type that isn't `Pod` (e.g. a [`PackedArchive`] record
+    /// type); `size` carries whatever count the subsystem wants to record.
+    fn named(typename: String, size: u64) -> Self {
+        let flags = if host_big_endian() {
+            flags::BIG_ENDIAN
+        } else {
+            0
+        };
         Self {
-            magic: *b"MMAP",
-            typename: std::any::type_name::<T>().into(),
+            magic: *MAGIC_V1,
+            version: CURRENT_VERSION,
+            flags,
+            typename,
             size,
+            checksum: 0,
         }
     }
 
     fn serialized_size(&self) -> u64 {
-        bincode::DefaultOptions::new()
-            .with_fixint_encoding()
-            .serialized_size(self)
-            .unwrap()
+        let mut buf = Vec::new();
+        self.serialize_into(&mut buf).unwrap();
+        buf.len() as u64
     }
 
     fn padded_size<T: Pod>(&self) -> u64 {
@@ -66,22 +191,72 @@ impl MmapFileHdr {
         }
     }
 
-    pub fn serialize_into<W>(&self, writer: W) -> Result<(), Box<bincode::ErrorKind>>
+    pub fn serialize_into<W>(&self, mut writer: W) -> Result<(), Box<bincode::ErrorKind>>
     where
         W: Write,
     {
-        bincode::DefaultOptions::new()
-            .with_fixint_encoding()
-            .serialize_into(writer, self)
+        let opts = bincode::DefaultOptions::new().with_fixint_encoding();
+        writer
+            .write_all(&self.magic)
+            .map_err(|e| Box::new(bincode::ErrorKind::Io(e)))?;
+        if &self.magic == MAGIC_V0 {
+            // Legacy layout, kept so v0 files can be rewritten in place.
+            opts.serialize_into(&mut writer, &self.typename)?;
+            opts.serialize_into(&mut writer, &self.size)?;
+        } else {
+            opts.serialize_into(&mut writer, &self.version)?;
+            opts.serialize_into(&mut writer, &self.flags)?;
+            opts.serialize_into(&mut writer, &self.typename)?;
+            opts.serialize_into(&mut writer, &self.size)?;
+            opts.serialize_into(&mut writer, &self.checksum)?;
+        }
+        Ok(())
     }
 
-    pub fn deserialize_from<R>(reader: R) -> Result<Self, Box<bincode::ErrorKind>>
+    pub fn deserialize_from<R>(mut reader: R) -> Result<Self, Box<bincode::ErrorKind>>
     where
         R: Read,
     {
-        bincode::DefaultOptions::new()
-            .with_fixint_encoding()
-            .deserialize_from::<R, Self>(reader)
+        let opts = bincode::DefaultOptions::new().with_fixint_encoding();
+        let mut magic = [0u8; 4];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|e| Box::new(bincode::ErrorKind::Io(e)))?;
+        if &magic == MAGIC_V0 {
+            // Absent version/flags/checksum: treat as format version 0.
+            let typename = opts.deserialize_from(&mut reader)?;
+            let size = opts.deserialize_from(&mut reader)?;
+            Ok(Self {
+                magic,
+                version: 0,
+                flags: 0,
+                typename,
+                size,
+                checksum: 0,
+            })
+        } else if &magic == MAGIC_V1 {
+            let version = opts.deserialize_from(&mut reader)?;
+            let flags = opts.deserialize_from(&mut reader)?;
+            let typename = opts.deserialize_from(&mut reader)?;
+            let size = opts.deserialize_from(&mut reader)?;
+            let checksum = opts.deserialize_from(&mut reader)?;
+            if version > CURRENT_VERSION {
+                return Err(Box::new(bincode::ErrorKind::Custom(format!(
+                    "unsupported format version {}",
+                    version
+                ))));
+            }
+            Ok(Self {
+                magic,
+                version,
+                flags,
+                typename,
+                size,
+                checksum,
+            })
+        } else {
+            Err(Box::new(bincode::ErrorKind::Custom("bad magic".into())))
+        }
     }
 }
 
@@ -97,14 +272,132 @@ impl<'a, T: Type> Mmap<'a> for Map<T> {
     fn as_slice_mut(&mut self) -> &'a mut [u8] {
         unsafe { from_raw_parts_mut(self.addr() as *mut u8, self.size()) }
     }
+
+    fn flush(&self, offset: usize, len: usize, sync: bool) -> std::io::Result<()> {
+        msync_range(self.addr() as *mut u8, offset, len, sync)
+    }
+
+    fn advise(&self, offset: usize, len: usize, advice: Advice) -> std::io::Result<()> {
+        madvise_range(self.addr() as *mut u8, offset, len, advice)
+    }
 }
 
 const fn page_align(val: u64) -> u64 {
     ((val + 4095) / 4096) * 4096
 }
 
+/// Build an `InvalidData` error for a cleanly-rejected header/open.
+fn invalid_data(msg: &'static str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// A mapping placed into a large, up-front address-space reservation.
+///
+/// The reservation (an anonymous `PROT_NONE`/`MAP_NORESERVE` region) fixes the
+/// base address; the live file length is mapped `MAP_FIXED` into its low part,
+/// so growing the file just maps the newly added pages at `base + mapped`
+/// without moving anything already handed out. The `Rc<Cell<_>>` fields are
+/// shared with the owning [`MmapFile`] so a `grow` is visible through the map.
+struct ReservedMap {
+    base: Rc<Cell<*mut u8>>,
+    len: Rc<Cell<usize>>,
+    reserved: Rc<Cell<usize>>,
+}
+
+impl Drop for ReservedMap {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base.get() as *mut libc::c_void, self.reserved.get());
+        }
+    }
+}
+
+impl<'a> Mmap<'a> for ReservedMap {
+    fn size(&self) -> usize {
+        self.len.get()
+    }
+
+    fn as_slice(&self) -> &'a [u8] {
+        unsafe { from_raw_parts(self.base.get(), self.len.get()) }
+    }
+
+    fn as_slice_mut(&mut self) -> &'a mut [u8] {
+        unsafe { from_raw_parts_mut(self.base.get(), self.len.get()) }
+    }
+
+    fn flush(&self, offset: usize, len: usize, sync: bool) -> std::io::Result<()> {
+        msync_range(self.base.get(), offset, len, sync)
+    }
+
+    fn advise(&self, offset: usize, len: usize, advice: Advice) -> std::io::Result<()> {
+        madvise_range(self.base.get(), offset, len, advice)
+    }
+}
+
+/// Reserve `bytes` of address space without committing any backing.
+fn reserve(bytes: usize) -> *mut u8 {
+    let p = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            bytes,
+            libc::PROT_NONE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE,
+            -1,
+            0,
+        )
+    };
+    if p == libc::MAP_FAILED {
+        panic!("could not reserve address space");
+    }
+    p as *mut u8
+}
+
+/// Map `len` bytes of `file` (starting at `file_offset`) at the fixed address
+/// `addr`, replacing whatever reservation currently covers that range.
+fn map_fixed(addr: *mut u8, file: &File, file_offset: u64, len: usize) {
+    let p = unsafe {
+        libc::mmap(
+            addr as *mut libc::c_void,
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            file.as_raw_fd(),
+            file_offset as libc::off_t,
+        )
+    };
+    if p == libc::MAP_FAILED {
+        panic!("could not map file into reserved range");
+    }
+}
+
+/// Read the on-disk header, let `f` mutate it, and write it back in place. The
+/// serialized length is unchanged (the typename is fixed), so this just
+/// overwrites the header prefix.
+fn rewrite_hdr<F: FnOnce(&mut MmapFileHdr)>(file: &File, f: F) {
+    let mut fh = file.try_clone().unwrap();
+    fh.seek(SeekFrom::Start(0)).unwrap();
+    let mut hdr = MmapFileHdr::deserialize_from(&fh).unwrap();
+    f(&mut hdr);
+    fh.seek(SeekFrom::Start(0)).unwrap();
+    hdr.serialize_into(&fh).unwrap();
+}
+
+/// Bookkeeping for a growable [`MmapFile`]. Present only when the file was
+/// opened with a growth ceiling; plain mappings leave this `None`.
+struct GrowState {
+    file: File,
+    base: Rc<Cell<*mut u8>>,
+    reserved: Rc<Cell<usize>>,
+    len: Rc<Cell<usize>>,
+    mapped: usize,
+    data_offset: u64,
+}
+
 pub struct MmapFile<'a, T: Pod> {
     map: Box<dyn Mmap<'a>>,
+    file: Option<File>,
+    hdr_len: u64,
+    grow: Option<GrowState>,
     _type: PhantomData<T>,
 }
 
@@ -132,24 +425,103 @@ impl<'a, T: Pod> MmapFile<'a, T> {
 
         MmapFile {
             map,
+            file: Some(file),
+            hdr_len: offset as u64,
+            grow: None,
             _type: PhantomData,
         }
     }
 
     pub fn open<P: AsRef<Path>>(filename: P) -> Result<MmapFile<'a, T>, std::io::Error> {
         let file = OpenOptions::new().read(true).write(true).open(filename)?;
-        let hdr = MmapFileHdr::deserialize_from(&file).unwrap();
+        let hdr = MmapFileHdr::deserialize_from(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
         if hdr.typename != std::any::type_name::<T>() {
-            panic!("type mismatch");
+            return Err(invalid_data("type mismatch"));
+        }
+        if hdr.flags & flags::APPEND_LOG != 0 {
+            return Err(invalid_data("file is an append-log, open with MmapLog"));
+        }
+        if (hdr.flags & flags::BIG_ENDIAN != 0) != host_big_endian() {
+            return Err(invalid_data(
+                "byte order mismatch; use MmapFile::open_normalized",
+            ));
         }
 
         let offset = page_align(MmapFileHdr::serialized_size(&hdr));
 
-        Ok(MmapFile::map::<perms::ReadWrite>(
-            file,
-            offset as i64,
-            hdr.size as usize,
-        ))
+        let mf = MmapFile::map::<perms::ReadWrite>(file, offset as i64, hdr.size as usize);
+
+        // Version 0 files predate the checksum field; only verify when present.
+        if hdr.version != 0 && mf.data_checksum() != hdr.checksum {
+            return Err(invalid_data("data checksum mismatch"));
+        }
+
+        Ok(mf)
+    }
+
+    /// Open a file whose producer byte order may differ from the host.
+    ///
+    /// On a matching byte order this is equivalent to [`open`](Self::open). On a
+    /// mismatch it byte-swaps every element in place, then rewrites the header's
+    /// byte-order flag and checksum so subsequent plain `open`s are zero-copy.
+    pub fn open_normalized<P: AsRef<Path>>(
+        filename: P,
+    ) -> Result<MmapFile<'a, T>, std::io::Error>
+    where
+        T: ByteSwap + Copy,
+    {
+        let file = OpenOptions::new().read(true).write(true).open(filename)?;
+        let hdr = MmapFileHdr::deserialize_from(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if hdr.typename != std::any::type_name::<T>() {
+            return Err(invalid_data("type mismatch"));
+        }
+        if hdr.flags & flags::APPEND_LOG != 0 {
+            return Err(invalid_data("file is an append-log, open with MmapLog"));
+        }
+
+        let offset = page_align(MmapFileHdr::serialized_size(&hdr));
+        let mut mf = MmapFile::map::<perms::ReadWrite>(file, offset as i64, hdr.size as usize);
+
+        // The checksum is over the raw bytes, so it verifies before any swap.
+        if hdr.version != 0 && mf.data_checksum() != hdr.checksum {
+            return Err(invalid_data("data checksum mismatch"));
+        }
+
+        let mismatch = (hdr.flags & flags::BIG_ENDIAN != 0) != host_big_endian();
+        if mismatch {
+            for elem in mf.as_slice_mut() {
+                *elem = elem.swap_bytes();
+            }
+            if let Some(file) = &mf.file {
+                rewrite_hdr(file, |h| {
+                    if host_big_endian() {
+                        h.flags |= flags::BIG_ENDIAN;
+                    } else {
+                        h.flags &= !flags::BIG_ENDIAN;
+                    }
+                });
+            }
+            mf.update_checksum();
+        }
+
+        Ok(mf)
+    }
+
+    /// crc32 of the mapped data region, used for the header integrity field.
+    fn data_checksum(&self) -> u64 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(cast_slice::<T, u8>(self.as_slice()));
+        hasher.finalize() as u64
+    }
+
+    /// Recompute the data-region checksum and write it into the on-disk header.
+    pub fn update_checksum(&self) {
+        if let Some(file) = &self.file {
+            let cs = self.data_checksum();
+            rewrite_hdr(file, |h| h.checksum = cs);
+        }
     }
 
     pub fn with_capacity<P: AsRef<Path>>(
@@ -169,11 +541,121 @@ impl<'a, T: Pod> MmapFile<'a, T> {
 
         file.set_len(page_align(hdr_len + data_len))?;
 
-        Ok(MmapFile::map::<perms::ReadWrite>(
-            file,
-            hdr_len as i64,
-            capacity as usize,
-        ))
+        let mf = MmapFile::map::<perms::ReadWrite>(file, hdr_len as i64, capacity as usize);
+        mf.update_checksum();
+        Ok(mf)
+    }
+
+    /// Like [`with_capacity`](Self::with_capacity), but reserves address space
+    /// for up to `max_capacity` elements so the file can later be grown with
+    /// [`grow`](Self::grow) without moving the base pointer.
+    ///
+    /// Outstanding `&[T]`/`&mut [T]` borrows stay valid across a `grow` as long
+    /// as the new capacity stays within `max_capacity`; a grow past that
+    /// ceiling falls back to a full remap and invalidates them.
+    pub fn with_capacity_max<P: AsRef<Path>>(
+        filename: P,
+        capacity: usize,
+        max_capacity: usize,
+    ) -> Result<MmapFile<'a, T>, std::io::Error> {
+        assert!(capacity <= max_capacity);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(filename)?;
+
+        let hdr = MmapFileHdr::new::<T>(capacity as u64);
+        hdr.serialize_into(&file).unwrap();
+        let hdr_len = page_align(hdr.serialized_size());
+        let data_len = page_align((capacity * core::mem::size_of::<T>()) as u64);
+
+        file.set_len(hdr_len + data_len)?;
+
+        let size = core::mem::size_of::<T>();
+        let reserved = page_align((max_capacity * size) as u64) as usize;
+        let mapped = data_len as usize;
+        let base = reserve(reserved);
+        map_fixed(base, &file, hdr_len, mapped);
+
+        let base = Rc::new(Cell::new(base));
+        let reserved = Rc::new(Cell::new(reserved));
+        let len = Rc::new(Cell::new(capacity * size));
+
+        let map = Box::new(ReservedMap {
+            base: base.clone(),
+            len: len.clone(),
+            reserved: reserved.clone(),
+        });
+
+        let mf = MmapFile {
+            map,
+            file: Some(file.try_clone()?),
+            hdr_len,
+            grow: Some(GrowState {
+                file,
+                base,
+                reserved,
+                len,
+                mapped,
+                data_offset: hdr_len,
+            }),
+            _type: PhantomData,
+        };
+        mf.update_checksum();
+        Ok(mf)
+    }
+
+    /// Grow the backing file to hold `new_capacity` elements.
+    ///
+    /// Within the reserved ceiling this only extends the file and maps the
+    /// newly added pages, so the base pointer — and any slice already handed
+    /// out — stays valid. Beyond the ceiling it falls back to reserving a
+    /// fresh, larger region and remapping, which moves the base and thereby
+    /// invalidates outstanding slices. The on-disk `hdr.size` and data checksum
+    /// are refreshed after a successful grow.
+    pub fn grow(&mut self, new_capacity: usize) -> Result<(), std::io::Error> {
+        let g = self.grow.as_mut().expect("mapping is not growable");
+        let size = core::mem::size_of::<T>();
+        let new_logical = new_capacity * size;
+        let new_mapped = page_align(new_logical as u64) as usize;
+
+        if new_logical <= g.len.get() {
+            return Ok(());
+        }
+
+        if new_mapped <= g.reserved.get() {
+            g.file.set_len(g.data_offset + new_mapped as u64)?;
+            if new_mapped > g.mapped {
+                let added = new_mapped - g.mapped;
+                let addr = unsafe { g.base.get().add(g.mapped) };
+                map_fixed(addr, &g.file, g.data_offset + g.mapped as u64, added);
+                g.mapped = new_mapped;
+            }
+        } else {
+            // Grew past the reserved ceiling: reserve a fresh region, remap the
+            // whole data area into it, and drop the old reservation.
+            g.file.set_len(g.data_offset + new_mapped as u64)?;
+            let new_base = reserve(new_mapped);
+            map_fixed(new_base, &g.file, g.data_offset, new_mapped);
+            unsafe {
+                libc::munmap(g.base.get() as *mut libc::c_void, g.reserved.get());
+            }
+            g.base.set(new_base);
+            g.reserved.set(new_mapped);
+            g.mapped = new_mapped;
+        }
+
+        g.len.set(new_logical);
+        let file = g.file.try_clone()?;
+        // The borrow of `self.grow` ends here so the checksum can be recomputed
+        // over the grown region.
+        let checksum = self.data_checksum();
+        rewrite_hdr(&file, |h| {
+            h.size = new_capacity as u64;
+            h.checksum = checksum;
+        });
+        Ok(())
     }
 
     pub fn size(&self) -> usize {
@@ -187,6 +669,425 @@ impl<'a, T: Pod> MmapFile<'a, T> {
     pub fn as_slice_mut(&mut self) -> &'a mut [T] {
         cast_slice_mut::<u8, T>(self.map.as_slice_mut())
     }
+
+    /// Refresh the data-region checksum and block until all dirty pages have
+    /// been written to disk (`msync(MS_SYNC)`).
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.update_checksum();
+        self.map.flush(0, self.map.size(), true)
+    }
+
+    /// Like [`flush`](Self::flush), but schedules the writeback and returns
+    /// immediately (`msync(MS_ASYNC)`).
+    pub fn flush_async(&self) -> std::io::Result<()> {
+        self.update_checksum();
+        self.map.flush(0, self.map.size(), false)
+    }
+
+    /// Flush only the pages covering elements `[start_elem, start_elem + len)`,
+    /// widened to page boundaries.
+    pub fn flush_range(&self, start_elem: usize, len: usize) -> std::io::Result<()> {
+        let size = core::mem::size_of::<T>();
+        let start = (start_elem * size) as u64 / 4096 * 4096;
+        let end = page_align(((start_elem + len) * size) as u64);
+        self.map.flush(start as usize, (end - start) as usize, true)
+    }
+
+    /// Hint the kernel about the access pattern over the whole mapping.
+    pub fn advise(&self, advice: Advice) -> std::io::Result<()> {
+        self.map.advise(0, self.map.size(), advice)
+    }
+}
+
+/// Append-only log layered on top of an mmap.
+///
+/// The data region starts with two native `AtomicU64` counters: `reserved`
+/// (slots handed out) and `committed` (slots whose payload is written).
+/// `reserve` claims a contiguous range by advancing `reserved`, and publishes
+/// it to `committed` only once the range has been written, so several threads
+/// or processes sharing the same file can append concurrently while `len`/
+/// `iter` always expose a fully-written prefix. The elements themselves start
+/// right after the (aligned) counter slots.
+pub struct MmapLog<'a, T: Pod> {
+    map: Box<dyn Mmap<'a>>,
+    data_offset: usize,
+    capacity: usize,
+    _type: PhantomData<T>,
+}
+
+impl<'a, T: Pod> MmapLog<'a, T> {
+    /// Byte offset of the first element, i.e. the two counter slots
+    /// (`reserved`, `committed`) padded up to `align_of::<T>()`.
+    fn elem_offset() -> usize {
+        let align = core::mem::align_of::<T>().max(core::mem::align_of::<AtomicU64>());
+        let counters = 2 * core::mem::size_of::<AtomicU64>();
+        ((counters + align - 1) / align) * align
+    }
+
+    fn map<U: Known + 'static>(mut file: File, offset: i64, capacity: usize) -> Self {
+        let data_offset = Self::elem_offset();
+        let data_len = data_offset + capacity * std::mem::size_of::<T>();
+        let len = file.metadata().unwrap().len();
+
+        if len < data_len as u64 + (offset as u64) {
+            panic!("file too small");
+        }
+
+        let map = Box::new(
+            Map::map(data_len)
+                .anywhere()
+                .from(&mut file, offset)
+                .known::<U>(Kind::Shared)
+                .unwrap(),
+        );
+
+        MmapLog {
+            map,
+            data_offset,
+            capacity,
+            _type: PhantomData,
+        }
+    }
+
+    pub fn with_capacity<P: AsRef<Path>>(
+        filename: P,
+        capacity: usize,
+    ) -> Result<MmapLog<'a, T>, std::io::Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(filename)?;
+
+        let mut hdr = MmapFileHdr::new::<T>(capacity as u64);
+        hdr.flags |= flags::APPEND_LOG;
+        hdr.serialize_into(&file).unwrap();
+        let hdr_len = page_align(hdr.serialized_size());
+        let data_len = (Self::elem_offset() + capacity * core::mem::size_of::<T>()) as u64;
+
+        file.set_len(page_align(hdr_len + data_len))?;
+
+        Ok(MmapLog::map::<perms::ReadWrite>(
+            file,
+            hdr_len as i64,
+            capacity,
+        ))
+    }
+
+    pub fn open<P: AsRef<Path>>(filename: P) -> Result<MmapLog<'a, T>, std::io::Error> {
+        let file = OpenOptions::new().read(true).write(true).open(filename)?;
+        let hdr = MmapFileHdr::deserialize_from(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if hdr.typename != std::any::type_name::<T>() {
+            panic!("type mismatch");
+        }
+        if hdr.flags & flags::APPEND_LOG == 0 {
+            panic!("file is not an append-log");
+        }
+
+        let offset = page_align(MmapFileHdr::serialized_size(&hdr));
+
+        Ok(MmapLog::map::<perms::ReadWrite>(
+            file,
+            offset as i64,
+            hdr.size as usize,
+        ))
+    }
+
+    fn base(&self) -> *mut u8 {
+        self.map.as_slice().as_ptr() as *mut u8
+    }
+
+    /// Counter of slots handed out by `reserve` (may be ahead of `committed`
+    /// while writers are still filling their slots).
+    fn reserved_counter(&self) -> &AtomicU64 {
+        unsafe { &*(self.base() as *const AtomicU64) }
+    }
+
+    /// Counter of slots whose payload has been fully written; this is what
+    /// `len`/`iter` expose.
+    fn committed_counter(&self) -> &AtomicU64 {
+        unsafe { &*(self.base().add(core::mem::size_of::<AtomicU64>()) as *const AtomicU64) }
+    }
+
+    /// Number of committed (written and published) elements.
+    pub fn len(&self) -> usize {
+        self.committed_counter().load(Ordering::Acquire) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Claim `n` element slots, bound-checking *before* advancing the counter so
+    /// a full log can never leave `reserved` past `capacity`.
+    fn claim(&self, n: usize) -> usize {
+        let counter = self.reserved_counter();
+        let mut cur = counter.load(Ordering::Acquire);
+        loop {
+            let pos = cur as usize;
+            assert!(pos + n <= self.capacity, "log full");
+            match counter.compare_exchange_weak(
+                cur,
+                (pos + n) as u64,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return pos,
+                Err(actual) => cur = actual,
+            }
+        }
+    }
+
+    /// Publish `[pos, pos + n)` once its payload is written. Commits are applied
+    /// in reservation order so `committed` always marks a contiguous prefix.
+    fn publish(&self, pos: usize, n: usize) {
+        let counter = self.committed_counter();
+        while counter.load(Ordering::Acquire) as usize != pos {
+            std::hint::spin_loop();
+        }
+        counter.store((pos + n) as u64, Ordering::Release);
+    }
+
+    /// Atomically claim `n` element slots and return a [`Reservation`] over the
+    /// backing range. The slots become visible to `len`/`iter` only when the
+    /// reservation is dropped, i.e. after the caller has written them.
+    pub fn reserve(&self, n: usize) -> Reservation<'_, 'a, T> {
+        let pos = self.claim(n);
+        let slice = unsafe {
+            let ptr = self.base().add(self.data_offset) as *mut T;
+            from_raw_parts_mut(ptr.add(pos), n)
+        };
+        Reservation {
+            log: self,
+            pos,
+            n,
+            slice,
+        }
+    }
+
+    /// Append a single element.
+    pub fn push(&self, value: T) {
+        let mut res = self.reserve(1);
+        res[0] = value;
+    }
+
+    /// The committed prefix.
+    pub fn as_slice(&self) -> &'a [T] {
+        let len = self.len();
+        unsafe {
+            let ptr = self.base().add(self.data_offset) as *const T;
+            from_raw_parts(ptr, len)
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'a, T> {
+        self.as_slice().iter()
+    }
+}
+
+/// A claimed, not-yet-published range of [`MmapLog`] slots. Writing goes
+/// through the `Deref`/`DerefMut` slice; dropping the reservation publishes the
+/// range so it becomes visible to `len`/`iter`.
+pub struct Reservation<'r, 'a, T: Pod> {
+    log: &'r MmapLog<'a, T>,
+    pos: usize,
+    n: usize,
+    slice: &'a mut [T],
+}
+
+impl<'r, 'a, T: Pod> Deref for Reservation<'r, 'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.slice
+    }
+}
+
+impl<'r, 'a, T: Pod> DerefMut for Reservation<'r, 'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.slice
+    }
+}
+
+impl<'r, 'a, T: Pod> Drop for Reservation<'r, 'a, T> {
+    fn drop(&mut self) {
+        self.log.publish(self.pos, self.n);
+    }
+}
+
+/// Width of an offset-table entry.
+type LSize = u64;
+
+fn archive_bincode() -> impl bincode::Options {
+    bincode::DefaultOptions::new().with_fixint_encoding()
+}
+
+/// Builder for a [`PackedArchive`]: bincode-encodes records into a growing data
+/// buffer and remembers each record's end offset.
+pub struct PackedArchiveWriter<T: Serialize> {
+    data: Vec<u8>,
+    offsets: Vec<LSize>,
+    _type: PhantomData<T>,
+}
+
+impl<T: Serialize> Default for PackedArchiveWriter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Serialize> PackedArchiveWriter<T> {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            offsets: Vec::new(),
+            _type: PhantomData,
+        }
+    }
+
+    /// Encode and append a record.
+    pub fn push(&mut self, record: &T) -> Result<(), Box<bincode::ErrorKind>> {
+        archive_bincode().serialize_into(&mut self.data, record)?;
+        self.offsets.push(self.data.len() as LSize);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Write the archive out. When `compression` is `Some(level)` the data
+    /// region is stored as a single zstd block at that level.
+    pub fn finalize<P: AsRef<Path>>(
+        self,
+        filename: P,
+        compression: Option<i32>,
+    ) -> Result<(), std::io::Error> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(filename)?;
+
+        let (blob, compressed) = match compression {
+            Some(level) => (zstd::encode_all(&self.data[..], level)?, 1u64),
+            None => (self.data.clone(), 0u64),
+        };
+
+        let mut hdr = MmapFileHdr::named(std::any::type_name::<T>().into(), self.offsets.len() as u64);
+        if compressed != 0 {
+            hdr.flags |= flags::COMPRESSED;
+        }
+        hdr.serialize_into(&file).unwrap();
+
+        file.write_all(&(self.offsets.len() as u64).to_ne_bytes())?;
+        file.write_all(&compressed.to_ne_bytes())?;
+        file.write_all(&(self.data.len() as u64).to_ne_bytes())?;
+        for off in &self.offsets {
+            file.write_all(&off.to_ne_bytes())?;
+        }
+        file.write_all(&blob)?;
+        Ok(())
+    }
+}
+
+/// Read-only, random-access view over a [`PackedArchiveWriter`] file.
+///
+/// The offset table sits at an unaligned byte offset in the mapping (the header
+/// length depends on the typename), so entries are read copy-based via
+/// `read_u64` rather than cast as a `[LSize]` slice. `get(i)` is O(1): it slices
+/// `[table[i-1]..table[i])` out of the (possibly decompressed) data region and
+/// bincode-decodes it into `T`.
+pub struct PackedArchive<'a, T> {
+    map: Box<dyn Mmap<'a>>,
+    table_off: usize,
+    count: usize,
+    data: std::borrow::Cow<'a, [u8]>,
+    _type: PhantomData<T>,
+}
+
+impl<'a, T> PackedArchive<'a, T>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    pub fn open<P: AsRef<Path>>(filename: P) -> Result<PackedArchive<'a, T>, std::io::Error> {
+        let mut file = OpenOptions::new().read(true).open(filename)?;
+        let hdr = MmapFileHdr::deserialize_from(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if hdr.typename != std::any::type_name::<T>() {
+            panic!("type mismatch");
+        }
+        let hdr_len = hdr.serialized_size() as usize;
+        let file_len = file.metadata().unwrap().len() as usize;
+
+        let map = Box::new(
+            Map::map(file_len)
+                .anywhere()
+                .from(&mut file, 0)
+                .known::<perms::Read>(Kind::Shared)
+                .unwrap(),
+        );
+
+        let bytes = map.as_slice();
+        let count = read_u64(bytes, hdr_len) as usize;
+        let compressed = read_u64(bytes, hdr_len + 8) != 0;
+        let _raw_len = read_u64(bytes, hdr_len + 16);
+        let table_off = hdr_len + 24;
+        let data_off = table_off + count * core::mem::size_of::<LSize>();
+
+        let raw = &bytes[data_off..];
+        let data = if compressed {
+            std::borrow::Cow::Owned(zstd::decode_all(raw)?)
+        } else {
+            std::borrow::Cow::Borrowed(raw)
+        };
+
+        Ok(PackedArchive {
+            map,
+            table_off,
+            count,
+            data,
+            _type: PhantomData,
+        })
+    }
+
+    /// Read offset-table entry `i`. The table sits at an unaligned byte offset
+    /// (the header length depends on the typename), so entries are read
+    /// copy-based rather than cast as a `[LSize]` slice.
+    fn table_entry(&self, i: usize) -> LSize {
+        read_u64(self.map.as_slice(), self.table_off + i * 8)
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Decode record `i`.
+    pub fn get(&self, i: usize) -> T {
+        let end = self.table_entry(i) as usize;
+        let start = if i == 0 { 0 } else { self.table_entry(i - 1) as usize };
+        archive_bincode().deserialize(&self.data[start..end]).unwrap()
+    }
+}
+
+/// Read a native-endian `u64` out of `bytes` at byte offset `off`.
+fn read_u64(bytes: &[u8], off: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[off..off + 8]);
+    u64::from_ne_bytes(buf)
 }
 
 impl<'a, T: Pod> Deref for MmapFile<'a, T> {
@@ -251,13 +1152,175 @@ mod tests {
         assert_eq!(slice, &[1u8; 4000]);
     }
 
+    #[test]
+    fn grow_keeps_base() {
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_grow");
+
+        let mut fa = MmapFile::<u8>::with_capacity_max(&file_path, 4096, 1 << 20).unwrap();
+        {
+            let slice = &mut *fa;
+            slice[0] = 42;
+        }
+        let before = fa.as_slice().as_ptr();
+        fa.grow(8192).unwrap();
+        assert_eq!(fa.as_slice().as_ptr(), before);
+        assert_eq!(fa.len(), 8192);
+        assert_eq!(fa[0], 42);
+        fa.flush().unwrap();
+        drop(fa);
+
+        // The grow refreshed the on-disk checksum, so a reopen verifies.
+        let reopened = MmapFile::<u8>::open(&file_path).unwrap();
+        assert_eq!(reopened.len(), 8192);
+        assert_eq!(reopened[0], 42);
+    }
+
+    #[test]
+    fn log_push() {
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_log_push");
+
+        let log = crate::MmapLog::<u64>::with_capacity(file_path, 4096).unwrap();
+        assert!(log.is_empty());
+        for i in 0..10u64 {
+            log.push(i);
+        }
+        assert_eq!(log.len(), 10);
+        let collected: Vec<u64> = log.iter().copied().collect();
+        assert_eq!(collected, (0..10).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn byte_swap_scalars() {
+        use crate::ByteSwap;
+        assert_eq!(ByteSwap::swap_bytes(0x0102_0304u32), 0x0403_0201u32);
+        assert_eq!(ByteSwap::swap_bytes(1.0f32).swap_bytes(), 1.0f32);
+    }
+
+    #[test]
+    fn open_normalized_matching_host() {
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_endian");
+
+        let mut fa = MmapFile::<u32>::with_capacity(&file_path, 4).unwrap();
+        for (i, v) in (&mut *fa).iter_mut().enumerate() {
+            *v = i as u32;
+        }
+        fa.flush().unwrap();
+
+        // Same byte order as the producer: values come back untouched.
+        let reopened = MmapFile::<u32>::open_normalized(&file_path).unwrap();
+        assert_eq!(&reopened[..], &[0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn flush_and_advise() {
+        use crate::Advice;
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_flush");
+
+        let mut fa = MmapFile::<u64>::with_capacity(&file_path, 1024).unwrap();
+        fa.advise(Advice::Sequential).unwrap();
+        {
+            let slice = &mut *fa;
+            slice[0] = 7;
+            slice[1023] = 9;
+        }
+        fa.flush_range(0, 2).unwrap();
+        fa.flush().unwrap();
+
+        // The flush refreshed the checksum, so a reopen verifies cleanly.
+        let reopened = MmapFile::<u64>::open(&file_path).unwrap();
+        assert_eq!(reopened[0], 7);
+        assert_eq!(reopened[1023], 9);
+    }
+
+    #[test]
+    fn open_rejects_bad_magic() {
+        use std::io::Write;
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_bad_magic");
+
+        let mut f = std::fs::File::create(&file_path).unwrap();
+        f.write_all(b"XXXX\0\0\0\0").unwrap();
+        drop(f);
+
+        let err = MmapFile::<u8>::open(&file_path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn open_rejects_corrupt_data() {
+        use std::io::{Seek, SeekFrom, Write};
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_corrupt");
+
+        let mut fa = MmapFile::<u64>::with_capacity(&file_path, 64).unwrap();
+        (&mut *fa)[0] = 0xdead_beef;
+        fa.flush().unwrap();
+        drop(fa);
+
+        // Flip a byte in the data region (first page after the header).
+        let mut f = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&file_path)
+            .unwrap();
+        f.seek(SeekFrom::Start(4096)).unwrap();
+        f.write_all(&[0xff]).unwrap();
+        drop(f);
+
+        let err = MmapFile::<u64>::open(&file_path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn open_verifies_checksum() {
+        let temp = TempDir::default();
+        let mut file_path = PathBuf::from(temp.as_ref());
+        file_path.push("test_checksum");
+
+        MmapFile::<u8>::with_capacity(&file_path, 128).unwrap();
+        // Freshly created, never mutated: the stored checksum must still verify.
+        let reopened = MmapFile::<u8>::open(&file_path).unwrap();
+        assert_eq!(reopened.len(), 128);
+    }
+
+    #[test]
+    fn packed_archive_roundtrip() {
+        for compression in [None, Some(3)] {
+            let temp = TempDir::default();
+            let mut file_path = PathBuf::from(temp.as_ref());
+            file_path.push(format!("test_archive_{:?}", compression));
+
+            let mut writer = crate::PackedArchiveWriter::<String>::new();
+            let records = ["hello", "world", "variable length records"];
+            for r in records {
+                writer.push(&r.to_string()).unwrap();
+            }
+            writer.finalize(&file_path, compression).unwrap();
+
+            let archive = crate::PackedArchive::<String>::open(&file_path).unwrap();
+            assert_eq!(archive.len(), records.len());
+            for (i, r) in records.iter().enumerate() {
+                assert_eq!(&archive.get(i), r);
+            }
+        }
+    }
+
     #[test]
     fn mmapfilehdr_basic() {
         let hdr: MmapFileHdr = MmapFileHdr::new::<u8>(12345);
-        assert_eq!(22, hdr.serialized_size());
+        assert_eq!(40, hdr.serialized_size());
         let hdr: MmapFileHdr = MmapFileHdr::new::<u8>(1);
-        assert_eq!(22, hdr.serialized_size());
+        assert_eq!(40, hdr.serialized_size());
         let hdr: MmapFileHdr = MmapFileHdr::new::<u8>(1 << 32);
-        assert_eq!(22, hdr.serialized_size());
+        assert_eq!(40, hdr.serialized_size());
     }
 }